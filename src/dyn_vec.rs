@@ -0,0 +1,279 @@
+use std::alloc::{self, Layout};
+use std::mem;
+use std::ptr::{self, NonNull, Pointee};
+
+use crate::vec::Vec;
+
+/// Where one pushed value lives in the byte buffer, plus the pointer metadata needed to
+/// reconstruct a fat pointer to it (the vtable pointer for `dyn Trait`, the length for `[T]`).
+struct Entry<T: ?Sized> {
+    offset: usize,
+    metadata: <T as Pointee>::Metadata,
+}
+
+/// A contiguous, cache-friendly alternative to `Vec<Box<dyn Trait>>` (or `Vec<Box<[u8]>>`):
+/// unsized values of possibly differing size are packed one after another into a single byte
+/// buffer, with a side table of `(offset, metadata)` recording how to get a fat pointer back.
+///
+/// The buffer can't be a plain `RawVec<u8>`: that always allocates with `align_of::<u8>() == 1`,
+/// which only keeps entries aligned *relative to the base pointer*, not in absolute terms. So
+/// `DynVec` manages its own allocation and grows it with a `Layout` whose alignment is the
+/// largest alignment of any value pushed so far.
+pub struct DynVec<T: ?Sized> {
+    ptr: NonNull<u8>,
+    cap: usize,
+    align: usize,
+    len: usize,
+    entries: Vec<Entry<T>>,
+}
+
+impl<T: ?Sized> DynVec<T> {
+    pub fn new() -> Self {
+        DynVec {
+            ptr: NonNull::dangling(),
+            cap: 0,
+            align: 1,
+            len: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.len() == 0
+    }
+
+    /// Grows the buffer, if necessary, to hold `needed` bytes aligned to `align`. Growth always
+    /// allocates a fresh buffer and copies the bytes over (never `realloc`), since `realloc`
+    /// can't change a layout's alignment.
+    fn reserve_bytes(&mut self, needed: usize, align: usize) {
+        let new_align = std::cmp::max(self.align, align);
+        if needed <= self.cap && new_align == self.align {
+            return;
+        }
+
+        let new_cap = std::cmp::max(self.cap.saturating_mul(2), needed);
+
+        // a zero-size layout is documented UB for the global allocator; if nothing with a
+        // nonzero size has been pushed yet, just remember the new alignment and move on,
+        // keeping the dangling base pointer aligned to it
+        if new_cap == 0 {
+            self.align = new_align;
+            self.ptr = NonNull::new(new_align as *mut u8).unwrap();
+            return;
+        }
+
+        let new_layout = Layout::from_size_align(new_cap, new_align).unwrap();
+
+        unsafe {
+            let new_ptr = alloc::alloc(new_layout);
+            let new_ptr = match NonNull::new(new_ptr) {
+                Some(ptr) => ptr,
+                None => alloc::handle_alloc_error(new_layout),
+            };
+
+            if self.cap != 0 {
+                ptr::copy_nonoverlapping(self.ptr.as_ptr(), new_ptr.as_ptr(), self.len);
+                let old_layout = Layout::from_size_align(self.cap, self.align).unwrap();
+                alloc::dealloc(self.ptr.as_ptr(), old_layout);
+            }
+
+            self.ptr = new_ptr;
+            self.cap = new_cap;
+            self.align = new_align;
+        }
+    }
+
+    pub fn push(&mut self, value: Box<T>) {
+        unsafe {
+            let value_ptr: *mut T = Box::into_raw(value);
+            let metadata = ptr::metadata(value_ptr);
+            let size = mem::size_of_val(&*value_ptr);
+            let align = mem::align_of_val(&*value_ptr);
+
+            // pad up to `value`'s alignment so the reconstructed pointer is well-aligned
+            let offset = (self.len + align - 1) & !(align - 1);
+            let needed = offset + size;
+            self.reserve_bytes(needed, align);
+
+            let dst = self.ptr.as_ptr().add(offset);
+            ptr::copy_nonoverlapping(value_ptr as *const u8, dst, size);
+
+            // the bytes now live in our buffer; free the box's allocation without running
+            // `T`'s destructor, since ownership of the value moved with the bytes
+            if size != 0 {
+                let layout = Layout::for_value(&*value_ptr);
+                alloc::dealloc(value_ptr as *mut u8, layout);
+            }
+
+            self.entries.push(Entry { offset, metadata });
+            self.len = needed;
+        }
+    }
+
+    pub fn get(&self, index: usize) -> &T {
+        let entry = &self.entries[index];
+        unsafe {
+            let base = self.ptr.as_ptr().add(entry.offset);
+            &*ptr::from_raw_parts(base as *const (), entry.metadata)
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut T {
+        let entry = &self.entries[index];
+        unsafe {
+            let base = self.ptr.as_ptr().add(entry.offset);
+            &mut *ptr::from_raw_parts_mut(base as *mut (), entry.metadata)
+        }
+    }
+}
+
+impl<T: ?Sized> std::ops::Index<usize> for DynVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index)
+    }
+}
+
+impl<T: ?Sized> std::ops::IndexMut<usize> for DynVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index)
+    }
+}
+
+impl<T: ?Sized> Drop for DynVec<T> {
+    fn drop(&mut self) {
+        for entry in self.entries.iter() {
+            unsafe {
+                let base = self.ptr.as_ptr().add(entry.offset);
+                let ptr_: *mut T = ptr::from_raw_parts_mut(base as *mut (), entry.metadata);
+                ptr::drop_in_place(ptr_);
+            }
+        }
+
+        if self.cap != 0 {
+            unsafe {
+                let layout = Layout::from_size_align(self.cap, self.align).unwrap();
+                alloc::dealloc(self.ptr.as_ptr(), layout);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    trait Greet {
+        fn greet(&self) -> String;
+    }
+
+    struct Dog;
+    impl Greet for Dog {
+        fn greet(&self) -> String {
+            "woof".to_string()
+        }
+    }
+
+    struct Cat {
+        name: String,
+    }
+    impl Greet for Cat {
+        fn greet(&self) -> String {
+            format!("{} says meow", self.name)
+        }
+    }
+
+    #[test]
+    fn test_trait_objects() {
+        let mut dv: DynVec<dyn Greet> = DynVec::new();
+        dv.push(Box::new(Dog));
+        dv.push(Box::new(Cat { name: "Tom".to_string() }));
+
+        assert_eq!(dv.get(0).greet(), "woof");
+        assert_eq!(dv.get(1).greet(), "Tom says meow");
+        assert_eq!(dv.len(), 2);
+    }
+
+    #[test]
+    fn test_unsized_slices() {
+        let mut dv: DynVec<[u8]> = DynVec::new();
+        dv.push(Box::from([1u8, 2, 3]));
+        dv.push(Box::from([4u8, 5, 6, 7, 8]));
+
+        assert_eq!(dv.get(0), &[1, 2, 3]);
+        assert_eq!(dv.get(1), &[4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_entries_are_aligned() {
+        trait Marker {}
+
+        #[repr(align(64))]
+        struct Big([u8; 128]);
+        impl Marker for Big {}
+
+        struct Small(u8);
+        impl Marker for Small {}
+
+        let mut dv: DynVec<dyn Marker> = DynVec::new();
+        // interleave smaller, lower-aligned pushes so the buffer grows before the
+        // largest-alignment value is ever pushed
+        dv.push(Box::new(Small(1)));
+        dv.push(Box::new(Big([0; 128])));
+        dv.push(Box::new(Small(2)));
+        dv.push(Box::new(Big([0; 128])));
+
+        for i in 0..4 {
+            let addr = dv.get(i) as *const dyn Marker as *const () as usize;
+            assert_eq!(addr % mem::align_of::<Big>(), 0);
+        }
+    }
+
+    #[test]
+    fn test_zero_size_aligned_value_does_not_allocate() {
+        trait Marker {}
+
+        #[repr(align(8))]
+        struct ZstMarker;
+        impl Marker for ZstMarker {}
+
+        // pushing a zero-size value as the very first element means `needed == 0`; this must
+        // not hand a zero-size `Layout` to the global allocator, which is documented UB
+        let mut dv: DynVec<dyn Marker> = DynVec::new();
+        dv.push(Box::new(ZstMarker));
+        dv.push(Box::new(ZstMarker));
+
+        assert_eq!(dv.len(), 2);
+        assert_eq!(dv.cap, 0);
+    }
+
+    #[test]
+    fn test_drop_runs_for_every_element() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        trait Noisy {}
+
+        struct Counter(Rc<RefCell<usize>>);
+        impl Noisy for Counter {}
+        impl Drop for Counter {
+            fn drop(&mut self) {
+                *self.0.borrow_mut() += 1;
+            }
+        }
+
+        let dropped = Rc::new(RefCell::new(0));
+        {
+            let mut dv: DynVec<dyn Noisy> = DynVec::new();
+            dv.push(Box::new(Counter(dropped.clone())));
+            dv.push(Box::new(Counter(dropped.clone())));
+        }
+
+        assert_eq!(*dropped.borrow(), 2);
+    }
+}
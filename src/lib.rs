@@ -0,0 +1,6 @@
+#![feature(ptr_metadata)]
+
+pub mod dyn_vec;
+pub mod macros;
+pub mod raw_vec;
+pub mod vec;
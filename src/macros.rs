@@ -0,0 +1,34 @@
+/// Counts its arguments at compile time; used by [`vec!`] to pre-size the buffer via
+/// `with_capacity` instead of growing one push at a time.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __vec_count {
+    () => (0usize);
+    ($head:expr $(, $tail:expr)*) => (1usize + $crate::__vec_count!($($tail),*));
+}
+
+/// Builds this crate's [`Vec`](crate::vec::Vec), mirroring `std`'s `vec!`.
+#[macro_export]
+macro_rules! vec {
+    () => {
+        $crate::vec::Vec::new()
+    };
+    ($elem:expr; $n:expr) => {{
+        // evaluate `$elem` once, like `std`'s `vec!`, and clone it for the remaining slots
+        let elem = $elem;
+        let n = $n;
+        let mut v = $crate::vec::Vec::with_capacity(n);
+        if n > 0 {
+            for _ in 0..n - 1 {
+                v.push(::std::clone::Clone::clone(&elem));
+            }
+            v.push(elem);
+        }
+        v
+    }};
+    ($($x:expr),+ $(,)?) => {{
+        let mut v = $crate::vec::Vec::with_capacity($crate::__vec_count!($($x),+));
+        $(v.push($x);)+
+        v
+    }};
+}
@@ -1,52 +1,131 @@
 use std::{alloc::{self, Layout}, mem, ptr::NonNull};
 
-pub struct RawVec<T> {
+/// Why a `reserve`/`try_reserve` can fail to grow a [`RawVec`](crate::raw_vec::RawVec).
+#[derive(Debug)]
+pub enum TryReserveError {
+    /// The requested capacity (or its backing byte size) overflows `isize::MAX`.
+    CapacityOverflow,
+    /// The allocator returned null for the given layout.
+    AllocError { layout: Layout },
+}
+
+/// The allocator returned null for the requested layout.
+#[derive(Debug)]
+pub struct AllocError;
+
+/// An allocator that can back a [`RawVec`]. Mirrors the shape of the handful of operations
+/// `RawVec` actually needs, so arena/pool allocators can be plugged in without pulling in
+/// the full unstable `std::alloc::Allocator` trait.
+pub trait Allocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+    fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError>;
+    fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+/// The default allocator, backed by the global allocator (`std::alloc`).
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Global;
+
+impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = unsafe { alloc::alloc(layout) };
+        NonNull::new(ptr).ok_or(AllocError)
+    }
+
+    fn grow(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = unsafe { alloc::realloc(ptr.as_ptr(), old, new.size()) };
+        NonNull::new(ptr).ok_or(AllocError)
+    }
+
+    fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe {
+            alloc::dealloc(ptr.as_ptr(), layout);
+        }
+    }
+}
+
+pub struct RawVec<T, A: Allocator = Global> {
     pub ptr: NonNull<T>,
     pub cap: usize,
+    alloc: A,
 }
 
-impl<T> RawVec<T> {
+impl<T> RawVec<T, Global> {
     pub fn new() -> Self {
-        assert!(mem::size_of::<T>() != 0, "not allow zero sized T");
+        Self::new_in(Global)
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+}
+
+impl<T, A: Allocator> RawVec<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        // zero sized types never need allocation, so pretend we have infinite capacity
+        let cap = if mem::size_of::<T>() == 0 { usize::MAX } else { 0 };
         RawVec {
             ptr: NonNull::dangling(),
-            cap: 0,
+            cap,
+            alloc,
+        }
+    }
+
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        let mut buf = RawVec::new_in(alloc);
+        if cap > 0 && mem::size_of::<T>() != 0 {
+            buf.grow_to(cap);
         }
+        buf
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
     }
 
     pub fn grow(&mut self) {
-        unsafe {
-            let elem_size = mem::size_of::<T>();
-
-            let (new_cap, ptr) = if self.cap == 0 {
-                let layout = Layout::array::<T>(1).unwrap();
-                let ptr = alloc::alloc(layout);
-                (1, ptr)
-            } else {
-                let new_cap = self.cap * 2;
-                let old_num_bytes = self.cap * elem_size;
-                let new_num_bytes = old_num_bytes * 2;
-                //let layout = std::alloc::Layout::new::<T>();
-                let layout = Layout::array::<T>(self.cap).unwrap();
-                let ptr = alloc::realloc(self.ptr.as_ptr() as *mut _, layout, new_num_bytes);
-                (new_cap, ptr)
-            };
-
-            if ptr.is_null() { panic!("oom"); }
-
-            self.ptr = std::ptr::NonNull::<T>::new(ptr as *mut _).unwrap();
-            self.cap = new_cap;
+        let new_cap = if self.cap == 0 { 1 } else { self.cap.saturating_mul(2) };
+        self.grow_to(new_cap);
+    }
+
+    /// Grows (or shrinks, though callers never do) the buffer to exactly `new_cap` elements,
+    /// panicking on overflow and calling `handle_alloc_error` on allocation failure.
+    pub fn grow_to(&mut self, new_cap: usize) {
+        match self.try_grow_to(new_cap) {
+            Ok(()) => {}
+            Err(TryReserveError::CapacityOverflow) => panic!("capacity overflow"),
+            Err(TryReserveError::AllocError { layout }) => alloc::handle_alloc_error(layout),
         }
     }
+
+    pub fn try_grow_to(&mut self, new_cap: usize) -> Result<(), TryReserveError> {
+        let elem_size = mem::size_of::<T>();
+
+        // a ZST's cap is already usize::MAX, so we never get here for one
+        assert!(elem_size != 0, "capacity overflow");
+
+        let new_layout = Layout::array::<T>(new_cap).map_err(|_| TryReserveError::CapacityOverflow)?;
+
+        let ptr = if self.cap == 0 {
+            self.alloc.allocate(new_layout)
+        } else {
+            let old_layout = Layout::array::<T>(self.cap).unwrap();
+            self.alloc.grow(self.ptr.cast(), old_layout, new_layout)
+        }
+        .map_err(|_| TryReserveError::AllocError { layout: new_layout })?;
+
+        self.ptr = ptr.cast();
+        self.cap = new_cap;
+        Ok(())
+    }
 }
 
-impl<T> Drop for RawVec<T> {
+impl<T, A: Allocator> Drop for RawVec<T, A> {
     fn drop(&mut self) {
-        if self.cap != 0 {
+        let elem_size = mem::size_of::<T>();
+        if self.cap != 0 && elem_size != 0 {
             let layout = Layout::array::<T>(self.cap).unwrap();
-            unsafe {
-                alloc::dealloc(self.ptr.as_ptr() as *mut _, layout);
-            }
+            self.alloc.deallocate(self.ptr.cast(), layout);
         }
     }
-}
\ No newline at end of file
+}
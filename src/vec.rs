@@ -1,23 +1,69 @@
 use std::ptr;
 use std::mem;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
 
-use crate::raw_vec::RawVec;
+use crate::raw_vec::{Allocator, Global, RawVec, TryReserveError};
 
-pub struct Vec<T> {
-    buf: RawVec<T>,
+pub struct Vec<T, A: Allocator = Global> {
+    buf: RawVec<T, A>,
     len: usize,
 }
 
-impl<T> Vec<T> {
+impl<T> Vec<T, Global> {
     pub fn new() -> Self {
-        assert!(std::mem::size_of::<T>() != 0, "no zero type");
         Vec {
             buf: RawVec::new(),
             len: 0,
         }
     }
 
+    pub fn with_capacity(cap: usize) -> Self {
+        Vec {
+            buf: RawVec::with_capacity(cap),
+            len: 0,
+        }
+    }
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        Vec {
+            buf: RawVec::new_in(alloc),
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        Vec {
+            buf: RawVec::with_capacity_in(cap, alloc),
+            len: 0,
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
+    /// Grows the backing buffer, if necessary, so it can hold at least `additional` more
+    /// elements. Panics on capacity overflow; calls `handle_alloc_error` on allocation failure.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).unwrap_or_else(|| panic!("capacity overflow"));
+        if needed > self.buf.cap {
+            let doubled = self.buf.cap.saturating_mul(2);
+            self.buf.grow_to(std::cmp::max(doubled, needed));
+        }
+    }
+
+    /// Fallible version of [`Vec::reserve`] that reports failure instead of panicking/aborting.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let needed = self.len.checked_add(additional).ok_or(TryReserveError::CapacityOverflow)?;
+        if needed > self.buf.cap {
+            let doubled = self.buf.cap.saturating_mul(2);
+            self.buf.try_grow_to(std::cmp::max(doubled, needed))?;
+        }
+        Ok(())
+    }
+
     pub fn push(&mut self, elem: T) {
         if self.len == self.buf.cap { self.buf.grow(); }
         unsafe {
@@ -49,7 +95,7 @@ impl<T> Vec<T> {
 
             let move_count = self.len - index;
             if move_count > 0 {
-                self.buf.ptr.as_ptr().add(index).copy_to(self.buf.ptr.as_ptr().add(index+1), move_count);    
+                self.buf.ptr.as_ptr().add(index).copy_to(self.buf.ptr.as_ptr().add(index+1), move_count);
             }
             self.buf.ptr.as_ptr().add(index).write(elem);
             self.len += 1;
@@ -67,36 +113,89 @@ impl<T> Vec<T> {
             if move_count > 0 {
                 self.buf.ptr.as_ptr().add(index+1).copy_to(self.buf.ptr.as_ptr().add(index), move_count);
             }
-            
+
             self.len -= 1;
 
             value
         }
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
+    pub fn into_iter(self) -> IntoIter<T, A> {
         unsafe {
             let buf = ptr::read(&self.buf);
             let len = self.len;
-    
+
             mem::forget(self);
 
+            let start = buf.ptr.as_ptr();
+            let end = if mem::size_of::<T>() == 0 {
+                (start as usize + len) as *const T
+            } else {
+                start.add(len)
+            };
+
             IntoIter {
-                start: buf.ptr.as_ptr(),
-                end: buf.ptr.as_ptr().add(len),
+                start,
+                end,
                 _buf: buf,
             }
         }
     }
+
+    /// Removes and yields the elements in `range`, shifting the remaining tail down to fill
+    /// the gap once the returned [`Drain`] is dropped (even if it was only partially consumed).
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A> {
+        let len = self.len;
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n + 1,
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => len,
+        };
+        assert!(start <= end, "drain start is after end");
+        assert!(end <= len, "drain end is out of bounds");
+
+        unsafe {
+            let range_start = self.buf.ptr.as_ptr().add(start) as *const T;
+            let range_end = if mem::size_of::<T>() == 0 {
+                (range_start as usize + (end - start)) as *const T
+            } else {
+                range_start.add(end - start)
+            };
+
+            // so `self` can't observe the elements `Drain` is about to move out of
+            self.len = start;
+
+            Drain {
+                tail_start: end,
+                tail_len: len - end,
+                start: range_start,
+                end: range_end,
+                vec: self,
+            }
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.deref().iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.deref_mut().iter_mut()
+    }
 }
 
-impl<T> Drop for Vec<T> {
+impl<T, A: Allocator> Drop for Vec<T, A> {
     fn drop(&mut self) {
         while let Some(_) = self.pop() {}
     }
 }
 
-impl<T> Deref for Vec<T> {
+impl<T, A: Allocator> Deref for Vec<T, A> {
     type Target = [T];
 
     fn deref(&self) -> &Self::Target {
@@ -107,7 +206,7 @@ impl<T> Deref for Vec<T> {
 }
 
 
-impl<T> DerefMut for Vec<T> {
+impl<T, A: Allocator> DerefMut for Vec<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe {
             std::slice::from_raw_parts_mut(self.buf.ptr.as_ptr(), self.len)
@@ -115,13 +214,60 @@ impl<T> DerefMut for Vec<T> {
     }
 }
 
-pub struct IntoIter<T> {
-    _buf: RawVec<T>,
+impl<T: Clone, A: Allocator + Clone> Clone for Vec<T, A> {
+    fn clone(&self) -> Self {
+        // `push`ing the clones one at a time (rather than bulk-copying) means a panicking
+        // `T::clone` just unwinds through `cloned`'s own `Drop`, which only ever sees
+        // fully-cloned elements.
+        let mut cloned = Vec::with_capacity_in(self.len, self.buf.allocator().clone());
+        for elem in self.iter() {
+            cloned.push(elem.clone());
+        }
+        cloned
+    }
+}
+
+impl<T> FromIterator<T> for Vec<T, Global> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        let mut v = Vec::with_capacity(lower);
+        for item in iter {
+            v.push(item);
+        }
+        v
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for Vec<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.reserve(lower);
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+pub struct IntoIter<T, A: Allocator = Global> {
+    _buf: RawVec<T, A>,
     start: *const T,
     end: *const T,
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: Allocator> IntoIter<T, A> {
+    fn elems_left(&self) -> usize {
+        let elem_size = mem::size_of::<T>();
+        if elem_size == 0 {
+            self.end as usize - self.start as usize
+        } else {
+            (self.end as usize - self.start as usize) / elem_size
+        }
+    }
+}
+
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -130,16 +276,121 @@ impl<T> Iterator for IntoIter<T> {
         } else {
             unsafe {
                 let value = self.start.read();
-                self.start = self.start.add(1);
+                self.start = if mem::size_of::<T>() == 0 {
+                    (self.start as usize + 1) as *const T
+                } else {
+                    self.start.add(1)
+                };
                 Some(value)
             }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.elems_left();
+        (len, Some(len))
+    }
 }
 
-impl<T> Drop for IntoIter<T> {
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            None
+        } else {
+            unsafe {
+                self.end = if mem::size_of::<T>() == 0 {
+                    (self.end as usize - 1) as *const T
+                } else {
+                    self.end.sub(1)
+                };
+                Some(self.end.read())
+            }
+        }
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {
+    fn len(&self) -> usize {
+        self.elems_left()
+    }
+}
+
+impl<T, A: Allocator> Drop for IntoIter<T, A> {
     fn drop(&mut self) {
+        // alternate ends so both `next` and `next_back` (added alongside `DoubleEndedIterator`)
+        // are exercised in draining the remainder, rather than forward-only
+        loop {
+            if self.next().is_none() {
+                break;
+            }
+            if self.next_back().is_none() {
+                break;
+            }
+        }
+    }
+}
+
+pub struct Drain<'a, T, A: Allocator = Global> {
+    vec: &'a mut Vec<T, A>,
+    tail_start: usize,
+    tail_len: usize,
+    start: *const T,
+    end: *const T,
+}
+
+impl<'a, T, A: Allocator> Iterator for Drain<'a, T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            None
+        } else {
+            unsafe {
+                let value = self.start.read();
+                self.start = if mem::size_of::<T>() == 0 {
+                    (self.start as usize + 1) as *const T
+                } else {
+                    self.start.add(1)
+                };
+                Some(value)
+            }
+        }
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for Drain<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            None
+        } else {
+            unsafe {
+                self.end = if mem::size_of::<T>() == 0 {
+                    (self.end as usize - 1) as *const T
+                } else {
+                    self.end.sub(1)
+                };
+                Some(self.end.read())
+            }
+        }
+    }
+}
+
+impl<'a, T, A: Allocator> Drop for Drain<'a, T, A> {
+    fn drop(&mut self) {
+        // make sure we drop any elements the caller never pulled out
         while let Some(_) = self.next() {}
+
+        if self.tail_len > 0 {
+            unsafe {
+                let start = self.vec.len;
+                if self.tail_start != start {
+                    let src = self.vec.buf.ptr.as_ptr().add(self.tail_start);
+                    let dst = self.vec.buf.ptr.as_ptr().add(start);
+                    ptr::copy(src, dst, self.tail_len);
+                }
+                self.vec.len = start + self.tail_len;
+            }
+        }
     }
 }
 
@@ -261,4 +512,350 @@ mod tests {
         v.push(1);
         v.remove(1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_with_capacity() {
+        let mut v = Vec::with_capacity(10);
+        assert_eq!(v.buf.cap, 10);
+
+        for n in 1..=10 {
+            v.push(n);
+        }
+        assert_eq!(v.buf.cap, 10);
+        assert_eq!(&*v, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn test_reserve() {
+        let mut v = Vec::<i32>::new();
+        v.reserve(5);
+        assert!(v.buf.cap >= 5);
+
+        v.push(1);
+        v.reserve(20);
+        assert!(v.buf.cap >= 21);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn test_reserve_panics_on_overflow_in_any_build_profile() {
+        let mut v = Vec::<i32>::new();
+        v.push(1);
+        v.reserve(usize::MAX);
+    }
+
+    #[test]
+    fn test_try_reserve() {
+        let mut v = Vec::<i32>::new();
+        assert!(v.try_reserve(5).is_ok());
+        assert!(v.buf.cap >= 5);
+
+        assert!(matches!(
+            v.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_try_reserve_does_not_overflow_on_doubling() {
+        let mut v = Vec::<i32>::new();
+        // fake a capacity near `usize::MAX` (without actually allocating it) so that
+        // `cap * 2` would itself overflow `usize` if it weren't a saturating multiply
+        v.buf.cap = usize::MAX - 5;
+
+        let result = v.try_reserve(usize::MAX - 1);
+        assert!(matches!(result, Err(TryReserveError::CapacityOverflow)));
+
+        // avoid a bogus `dealloc` of memory we never actually allocated
+        v.buf.cap = 0;
+    }
+
+    #[test]
+    fn test_zero_sized_type() {
+        let mut v = Vec::new();
+        for _ in 0..100 {
+            v.push(());
+        }
+
+        assert_eq!(v.len(), 100);
+
+        let mut count = 0;
+        for _ in v.into_iter() {
+            count += 1;
+        }
+        assert_eq!(count, 100);
+    }
+
+    #[test]
+    fn test_drain_middle() {
+        let mut v = Vec::new();
+        for n in 1..=5 {
+            v.push(n);
+        }
+
+        let drained: std::vec::Vec<_> = v.drain(1..4).collect();
+        assert_eq!(drained, std::vec::Vec::from([2, 3, 4]));
+        assert_eq!(&*v, &[1, 5]);
+    }
+
+    #[test]
+    fn test_drain_full_range() {
+        let mut v = Vec::new();
+        for n in 1..=3 {
+            v.push(n);
+        }
+
+        let drained: std::vec::Vec<_> = v.drain(..).collect();
+        assert_eq!(drained, std::vec::Vec::from([1, 2, 3]));
+        assert_eq!(&*v, &[]);
+    }
+
+    #[test]
+    fn test_drain_partially_consumed() {
+        let mut v = Vec::new();
+        for n in 1..=5 {
+            v.push(n);
+        }
+
+        {
+            let mut drain = v.drain(1..4);
+            assert_eq!(drain.next(), Some(2));
+        }
+
+        assert_eq!(&*v, &[1, 5]);
+    }
+
+    #[test]
+    fn test_drain_next_back() {
+        let mut v = Vec::new();
+        for n in 1..=5 {
+            v.push(n);
+        }
+
+        {
+            let mut drain = v.drain(1..4);
+            assert_eq!(drain.next(), Some(2));
+            assert_eq!(drain.next_back(), Some(4));
+            assert_eq!(drain.next_back(), Some(3));
+            assert_eq!(drain.next_back(), None);
+            assert_eq!(drain.next(), None);
+        }
+
+        assert_eq!(&*v, &[1, 5]);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut v = Vec::new();
+        v.push(1);
+        v.push(2);
+        v.push(3);
+
+        let summed: i32 = v.iter().sum();
+        assert_eq!(summed, 6);
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut v = Vec::new();
+        v.push(1);
+        v.push(2);
+        v.push(3);
+
+        for n in v.iter_mut() {
+            *n *= 10;
+        }
+        assert_eq!(&*v, &[10, 20, 30]);
+    }
+
+    #[test]
+    fn test_into_iter_rev_and_len() {
+        let mut v = Vec::new();
+        for n in 1..=5 {
+            v.push(n);
+        }
+
+        let mut iter = v.into_iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(5));
+        assert_eq!(iter.len(), 3);
+
+        let rest: std::vec::Vec<_> = iter.collect();
+        assert_eq!(rest, std::vec::Vec::from([2, 3, 4]));
+    }
+
+    #[test]
+    fn test_into_iter_drop_drops_remaining_elements() {
+        use std::cell::Cell;
+
+        struct DropCounter<'a>(&'a Cell<usize>);
+        impl<'a> Drop for DropCounter<'a> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let dropped = Cell::new(0);
+        let mut v = Vec::new();
+        for _ in 0..5 {
+            v.push(DropCounter(&dropped));
+        }
+
+        let mut iter = v.into_iter();
+        iter.next();
+        iter.next_back();
+        assert_eq!(dropped.get(), 2);
+
+        drop(iter);
+        assert_eq!(dropped.get(), 5);
+    }
+
+    #[test]
+    fn test_vec_macro_list() {
+        let v = crate::vec![1, 2, 3];
+        assert_eq!(&*v, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_vec_macro_repeat() {
+        let v = crate::vec![7; 4];
+        assert_eq!(&*v, &[7, 7, 7, 7]);
+    }
+
+    #[test]
+    fn test_vec_macro_repeat_evaluates_elem_once() {
+        use std::cell::Cell;
+
+        let counter = Cell::new(0);
+        let v = crate::vec![{ let n = counter.get(); counter.set(n + 1); n }; 5];
+
+        assert_eq!(&*v, &[0, 0, 0, 0, 0]);
+        assert_eq!(counter.get(), 1);
+    }
+
+    #[test]
+    fn test_vec_macro_empty() {
+        let v: Vec<i32> = crate::vec![];
+        assert_eq!(&*v, &[]);
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut v = Vec::new();
+        v.push(1);
+        v.push(2);
+        v.push(3);
+
+        let cloned = v.clone();
+        assert_eq!(&*cloned, &[1, 2, 3]);
+
+        drop(v);
+        assert_eq!(&*cloned, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_clone_panic_safety() {
+        struct PanicOnThirdClone {
+            n: i32,
+        }
+
+        impl Clone for PanicOnThirdClone {
+            fn clone(&self) -> Self {
+                if self.n == 3 {
+                    panic!("boom");
+                }
+                PanicOnThirdClone { n: self.n }
+            }
+        }
+
+        let mut v = Vec::new();
+        v.push(PanicOnThirdClone { n: 1 });
+        v.push(PanicOnThirdClone { n: 2 });
+        v.push(PanicOnThirdClone { n: 3 });
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| v.clone()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_from_iterator_and_extend() {
+        let mut v: Vec<i32> = (1..=3).collect();
+        assert_eq!(&*v, &[1, 2, 3]);
+
+        v.extend(4..=5);
+        assert_eq!(&*v, &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_new_in_with_global() {
+        let mut v = Vec::new_in(Global);
+        v.push(1);
+        v.push(2);
+        assert_eq!(&*v, &[1, 2]);
+    }
+
+    #[test]
+    fn test_custom_allocator_push_grow_drop() {
+        use std::alloc::{self, Layout};
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        use crate::raw_vec::AllocError;
+
+        #[derive(Clone)]
+        struct CountingAllocator {
+            allocations: Rc<Cell<usize>>,
+            deallocations: Rc<Cell<usize>>,
+        }
+
+        impl Allocator for CountingAllocator {
+            fn allocate(&self, layout: Layout) -> Result<std::ptr::NonNull<u8>, AllocError> {
+                self.allocations.set(self.allocations.get() + 1);
+                let ptr = unsafe { alloc::alloc(layout) };
+                std::ptr::NonNull::new(ptr).ok_or(AllocError)
+            }
+
+            fn grow(
+                &self,
+                ptr: std::ptr::NonNull<u8>,
+                old: Layout,
+                new: Layout,
+            ) -> Result<std::ptr::NonNull<u8>, AllocError> {
+                self.allocations.set(self.allocations.get() + 1);
+                let new_ptr = unsafe { alloc::realloc(ptr.as_ptr(), old, new.size()) };
+                std::ptr::NonNull::new(new_ptr).ok_or(AllocError)
+            }
+
+            fn deallocate(&self, ptr: std::ptr::NonNull<u8>, layout: Layout) {
+                self.deallocations.set(self.deallocations.get() + 1);
+                unsafe { alloc::dealloc(ptr.as_ptr(), layout) };
+            }
+        }
+
+        let allocations = Rc::new(Cell::new(0));
+        let deallocations = Rc::new(Cell::new(0));
+        let alloc = CountingAllocator {
+            allocations: allocations.clone(),
+            deallocations: deallocations.clone(),
+        };
+
+        {
+            let mut v = Vec::new_in(alloc);
+            for n in 0..100 {
+                v.push(n);
+            }
+
+            assert_eq!(v.len(), 100);
+            assert_eq!(v[0], 0);
+            assert_eq!(v[99], 99);
+
+            // several pushes must have grown the buffer through our allocator, not the global one
+            assert!(allocations.get() > 1);
+            assert_eq!(deallocations.get(), 0);
+        }
+
+        // dropping the Vec must free its single buffer through the same allocator
+        assert_eq!(deallocations.get(), 1);
+    }
+}